@@ -1,6 +1,9 @@
+use crate::history::HistoryState;
+use crate::menu::RecentFilesMenu;
+use crate::store;
 use crate::types::Diagnostic;
 use std::sync::Mutex;
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 
 // Global state for editor content (used by history system)
 pub struct EditorState {
@@ -23,8 +26,14 @@ impl Default for EditorState {
 
 /// Update editor state with current code (called when user types)
 #[tauri::command]
-pub fn update_editor_state(code: String, state: State<'_, EditorState>) -> Result<(), String> {
+pub fn update_editor_state(
+    code: String,
+    app: AppHandle,
+    state: State<'_, EditorState>,
+    history: State<'_, HistoryState>,
+) -> Result<(), String> {
     *state.current_code.lock().unwrap() = code;
+    history.mark_dirty_and_schedule_flush(&app);
     Ok(())
 }
 
@@ -32,8 +41,19 @@ pub fn update_editor_state(code: String, state: State<'_, EditorState>) -> Resul
 #[tauri::command]
 pub fn update_working_dir(
     working_dir: Option<String>,
+    app: AppHandle,
     state: State<'_, EditorState>,
 ) -> Result<(), String> {
-    *state.working_dir.lock().unwrap() = working_dir;
+    *state.working_dir.lock().unwrap() = working_dir.clone();
+
+    if let Some(path) = working_dir {
+        store::push_recent_file(&app, &path)?;
+        if let Some(recent_menu) = app.try_state::<RecentFilesMenu>() {
+            recent_menu
+                .rebuild(&app, &store::list_recent_files(&app))
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
     Ok(())
 }
@@ -0,0 +1,363 @@
+use crate::cmd::{menu, EditorState};
+use crate::history::HistoryState;
+use crate::store;
+use crate::types::{ChangeType, CheckpointDiff, Diagnostic, DiffHunk, DiffHunkTag, EditorCheckpoint};
+use tauri::{AppHandle, State};
+
+/// Record a new checkpoint, truncating any redo tail, and make it the
+/// current editor state.
+#[tauri::command]
+pub fn create_checkpoint(
+    code: String,
+    description: String,
+    change_type: ChangeType,
+    diagnostics: Vec<Diagnostic>,
+    app: AppHandle,
+    history: State<'_, HistoryState>,
+    editor: State<'_, EditorState>,
+) -> Result<EditorCheckpoint, String> {
+    let checkpoint = EditorCheckpoint {
+        id: history.next_checkpoint_id(),
+        timestamp: store::now_ms(),
+        code: code.clone(),
+        diagnostics: diagnostics.clone(),
+        description,
+        change_type,
+    };
+
+    history.push_checkpoint(checkpoint.clone());
+
+    *editor.current_code.lock().unwrap() = code;
+    *editor.diagnostics.lock().unwrap() = diagnostics;
+
+    history.mark_dirty_and_schedule_flush(&app);
+    let _ = menu::refresh(&app);
+
+    Ok(checkpoint)
+}
+
+#[tauri::command]
+pub fn can_undo(history: State<'_, HistoryState>) -> Result<bool, String> {
+    Ok(history.can_undo())
+}
+
+#[tauri::command]
+pub fn can_redo(history: State<'_, HistoryState>) -> Result<bool, String> {
+    Ok(history.can_redo())
+}
+
+#[tauri::command]
+pub fn undo(
+    app: AppHandle,
+    history: State<'_, HistoryState>,
+    editor: State<'_, EditorState>,
+) -> Result<Option<EditorCheckpoint>, String> {
+    let Some(checkpoint) = history.undo() else {
+        return Ok(None);
+    };
+
+    *editor.current_code.lock().unwrap() = checkpoint.code.clone();
+    *editor.diagnostics.lock().unwrap() = checkpoint.diagnostics.clone();
+    history.mark_dirty_and_schedule_flush(&app);
+    let _ = menu::refresh(&app);
+
+    Ok(Some(checkpoint))
+}
+
+#[tauri::command]
+pub fn redo(
+    app: AppHandle,
+    history: State<'_, HistoryState>,
+    editor: State<'_, EditorState>,
+) -> Result<Option<EditorCheckpoint>, String> {
+    let Some(checkpoint) = history.redo() else {
+        return Ok(None);
+    };
+
+    *editor.current_code.lock().unwrap() = checkpoint.code.clone();
+    *editor.diagnostics.lock().unwrap() = checkpoint.diagnostics.clone();
+    history.mark_dirty_and_schedule_flush(&app);
+    let _ = menu::refresh(&app);
+
+    Ok(Some(checkpoint))
+}
+
+#[tauri::command]
+pub fn get_history(history: State<'_, HistoryState>) -> Result<Vec<EditorCheckpoint>, String> {
+    Ok(history.checkpoints())
+}
+
+#[tauri::command]
+pub fn get_checkpoint_by_id(
+    id: String,
+    history: State<'_, HistoryState>,
+) -> Result<Option<EditorCheckpoint>, String> {
+    Ok(history.checkpoints().into_iter().find(|c| c.id == id))
+}
+
+#[tauri::command]
+pub fn restore_to_checkpoint(
+    id: String,
+    app: AppHandle,
+    history: State<'_, HistoryState>,
+    editor: State<'_, EditorState>,
+) -> Result<EditorCheckpoint, String> {
+    let checkpoint = history.restore_to(&id)?;
+
+    *editor.current_code.lock().unwrap() = checkpoint.code.clone();
+    *editor.diagnostics.lock().unwrap() = checkpoint.diagnostics.clone();
+    history.mark_dirty_and_schedule_flush(&app);
+    let _ = menu::refresh(&app);
+
+    Ok(checkpoint)
+}
+
+#[tauri::command]
+pub fn get_checkpoint_diff(
+    from_id: String,
+    to_id: String,
+    history: State<'_, HistoryState>,
+) -> Result<CheckpointDiff, String> {
+    let checkpoints = history.checkpoints();
+    let from = checkpoints
+        .iter()
+        .find(|c| c.id == from_id)
+        .ok_or_else(|| format!("no checkpoint with id {from_id}"))?;
+    let to = checkpoints
+        .iter()
+        .find(|c| c.id == to_id)
+        .ok_or_else(|| format!("no checkpoint with id {to_id}"))?;
+
+    Ok(diff_checkpoints(from, to))
+}
+
+/// A single line-level edit produced by [`diff_lines`], tagged the way a
+/// unified diff would tag it.
+#[derive(Clone, Copy)]
+enum LineEdit<'a> {
+    Unchanged(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Minimal LCS-based line diff. Good enough for the checkpoint-sized
+/// buffers this operates on; not meant to scale to huge files.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<LineEdit<'a>> {
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            edits.push(LineEdit::Unchanged(old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            edits.push(LineEdit::Removed(old[i]));
+            i += 1;
+        } else {
+            edits.push(LineEdit::Added(new[j]));
+            j += 1;
+        }
+    }
+    edits.extend(old[i..n].iter().map(|l| LineEdit::Removed(l)));
+    edits.extend(new[j..m].iter().map(|l| LineEdit::Added(l)));
+    edits
+}
+
+fn diff_checkpoints(from: &EditorCheckpoint, to: &EditorCheckpoint) -> CheckpointDiff {
+    let old_lines: Vec<&str> = from.code.lines().collect();
+    let new_lines: Vec<&str> = to.code.lines().collect();
+    let edits = diff_lines(&old_lines, &new_lines);
+
+    let mut diff = String::new();
+    let mut added_lines = 0;
+    let mut removed_lines = 0;
+    for edit in &edits {
+        match edit {
+            LineEdit::Unchanged(line) => diff.push_str(&format!(" {line}\n")),
+            LineEdit::Removed(line) => {
+                removed_lines += 1;
+                diff.push_str(&format!("-{line}\n"));
+            }
+            LineEdit::Added(line) => {
+                added_lines += 1;
+                diff.push_str(&format!("+{line}\n"));
+            }
+        }
+    }
+
+    let hunks = build_hunks(&edits, &from.diagnostics, &to.diagnostics);
+
+    CheckpointDiff {
+        from_id: from.id.clone(),
+        to_id: to.id.clone(),
+        diff,
+        added_lines,
+        removed_lines,
+        hunks,
+    }
+}
+
+/// Group consecutive removed/added lines into [`DiffHunk`]s (a removed run
+/// immediately followed by an added run is one `Modified` hunk, matching
+/// how an inline diff gutter would present a replaced block) and flag the
+/// ones whose line range overlaps a diagnostic on either side.
+fn build_hunks(
+    edits: &[LineEdit],
+    from_diagnostics: &[Diagnostic],
+    to_diagnostics: &[Diagnostic],
+) -> Vec<DiffHunk> {
+    let diagnostic_line_in = |diagnostics: &[Diagnostic], start: usize, end: usize| {
+        start <= end
+            && diagnostics
+                .iter()
+                .filter_map(|d| d.line)
+                .any(|line| (line as usize) >= start && (line as usize) <= end)
+    };
+
+    let mut hunks = Vec::new();
+    let (mut old_line, mut new_line) = (0usize, 0usize);
+    let mut i = 0;
+    while i < edits.len() {
+        match edits[i] {
+            LineEdit::Unchanged(_) => {
+                old_line += 1;
+                new_line += 1;
+                i += 1;
+            }
+            LineEdit::Removed(_) | LineEdit::Added(_) => {
+                let (old_start, new_start) = (old_line + 1, new_line + 1);
+                let (mut removed, mut added) = (0usize, 0usize);
+                while let Some(edit) = edits.get(i) {
+                    match edit {
+                        LineEdit::Removed(_) => {
+                            removed += 1;
+                            old_line += 1;
+                            i += 1;
+                        }
+                        LineEdit::Added(_) => {
+                            added += 1;
+                            new_line += 1;
+                            i += 1;
+                        }
+                        LineEdit::Unchanged(_) => break,
+                    }
+                }
+
+                let tag = match (removed > 0, added > 0) {
+                    (true, true) => DiffHunkTag::Modified,
+                    (true, false) => DiffHunkTag::Removed,
+                    (false, true) => DiffHunkTag::Added,
+                    (false, false) => unreachable!("hunk boundary always has at least one edit"),
+                };
+                // `+ removed - 1` is `old_start - 1` (an empty range) when
+                // removed == 0, since old_start >= 1.
+                let old_end = old_start + removed - 1;
+                let new_end = new_start + added - 1;
+
+                let overlaps_diagnostic = diagnostic_line_in(from_diagnostics, old_start, old_end)
+                    || diagnostic_line_in(to_diagnostics, new_start, new_end);
+
+                hunks.push(DiffHunk {
+                    tag,
+                    old_start,
+                    old_end,
+                    new_start,
+                    new_end,
+                    overlaps_diagnostic,
+                });
+            }
+        }
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DiagnosticSeverity;
+
+    fn lines(code: &str) -> Vec<&str> {
+        code.lines().collect()
+    }
+
+    fn diagnostic_at(line: i32) -> Diagnostic {
+        Diagnostic {
+            severity: DiagnosticSeverity::Error,
+            line: Some(line),
+            col: None,
+            message: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn identical_buffers_produce_no_hunks() {
+        let old = lines("a\nb\nc");
+        let new = lines("a\nb\nc");
+        let edits = diff_lines(&old, &new);
+        assert!(build_hunks(&edits, &[], &[]).is_empty());
+    }
+
+    #[test]
+    fn pure_insert_is_tagged_added() {
+        let old = lines("a");
+        let new = lines("a\nb");
+        let edits = diff_lines(&old, &new);
+        let hunks = build_hunks(&edits, &[], &[]);
+
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].tag, DiffHunkTag::Added);
+        assert!(hunks[0].old_end < hunks[0].old_start, "old side should be empty");
+        assert_eq!((hunks[0].new_start, hunks[0].new_end), (2, 2));
+    }
+
+    #[test]
+    fn pure_delete_is_tagged_removed() {
+        let old = lines("a\nb");
+        let new = lines("a");
+        let edits = diff_lines(&old, &new);
+        let hunks = build_hunks(&edits, &[], &[]);
+
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].tag, DiffHunkTag::Removed);
+        assert_eq!((hunks[0].old_start, hunks[0].old_end), (2, 2));
+        assert!(hunks[0].new_end < hunks[0].new_start, "new side should be empty");
+    }
+
+    #[test]
+    fn same_line_replace_is_tagged_modified() {
+        let old = lines("foo");
+        let new = lines("bar");
+        let edits = diff_lines(&old, &new);
+        let hunks = build_hunks(&edits, &[], &[]);
+
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].tag, DiffHunkTag::Modified);
+        assert_eq!((hunks[0].old_start, hunks[0].old_end), (1, 1));
+        assert_eq!((hunks[0].new_start, hunks[0].new_end), (1, 1));
+    }
+
+    #[test]
+    fn hunk_overlapping_a_diagnostic_is_flagged() {
+        let old = lines("a\nfoo\nc");
+        let new = lines("a\nbar\nc");
+        let edits = diff_lines(&old, &new);
+
+        let overlapping = build_hunks(&edits, &[], &[diagnostic_at(2)]);
+        assert!(overlapping[0].overlaps_diagnostic);
+
+        let non_overlapping = build_hunks(&edits, &[], &[diagnostic_at(3)]);
+        assert!(!non_overlapping[0].overlaps_diagnostic);
+    }
+}
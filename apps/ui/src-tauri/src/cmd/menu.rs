@@ -0,0 +1,45 @@
+use crate::cmd::EditorState;
+use crate::history::HistoryState;
+use crate::menu::MenuHandles;
+use crate::types::{DiagnosticSeverity, MenuState};
+use tauri::{AppHandle, Emitter, Manager};
+
+fn compute(history: &HistoryState, editor: &EditorState) -> MenuState {
+    let has_error = editor
+        .diagnostics
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|d| d.severity == DiagnosticSeverity::Error);
+
+    MenuState {
+        can_undo: history.can_undo(),
+        can_redo: history.can_redo(),
+        can_export: !has_error,
+    }
+}
+
+/// Recompute Undo/Redo/Export enabled state from `HistoryState` and
+/// `EditorState.diagnostics`, apply it to the live menu, and emit it so a
+/// toolbar mirror can stay in sync.
+pub fn refresh(app: &AppHandle) -> Result<MenuState, String> {
+    let history = app.state::<HistoryState>();
+    let editor = app.state::<EditorState>();
+    let state = compute(&history, &editor);
+
+    let handles = app.state::<MenuHandles>();
+    handles
+        .apply(state.can_undo, state.can_redo, state.can_export)
+        .map_err(|e| e.to_string())?;
+
+    let _ = app.emit("menu:state", &state);
+    Ok(state)
+}
+
+/// Frontend-callable counterpart of [`refresh`]. It recomputes from the
+/// current `HistoryState` and the `EditorState.diagnostics` last stored by a
+/// history command; it does not take new diagnostics from the caller.
+#[tauri::command]
+pub fn refresh_menu_state(app: AppHandle) -> Result<MenuState, String> {
+    refresh(&app)
+}
@@ -0,0 +1,5 @@
+pub mod ai_tools;
+pub mod history;
+pub mod menu;
+
+pub use ai_tools::{update_editor_state, update_working_dir, EditorState};
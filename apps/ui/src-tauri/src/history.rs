@@ -0,0 +1,191 @@
+//! In-memory undo/redo chain for the editor, mirrored to disk so it
+//! survives a crash or restart.
+
+use crate::cmd::{menu, EditorState};
+use crate::store::{self, SessionSnapshot};
+use crate::types::EditorCheckpoint;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+const FLUSH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// `checkpoints` and `cursor` always move together, so they live behind one
+/// lock: taking them separately let a debounced flush observe a cursor that
+/// didn't match the checkpoints it was paired with if a `create_checkpoint`/
+/// `undo`/`redo` landed between the two locks being acquired.
+struct HistoryEntries {
+    checkpoints: Vec<EditorCheckpoint>,
+    cursor: usize,
+}
+
+pub struct HistoryState {
+    entries: Mutex<HistoryEntries>,
+    next_id: AtomicU64,
+    /// Bumped on every dirtying change; a pending flush only writes if the
+    /// generation it was scheduled under is still the latest one.
+    generation: AtomicU64,
+}
+
+impl HistoryState {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HistoryEntries {
+                checkpoints: Vec::new(),
+                cursor: 0,
+            }),
+            next_id: AtomicU64::new(1),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    pub fn next_checkpoint_id(&self) -> String {
+        format!("cp_{}", self.next_id.fetch_add(1, Ordering::SeqCst))
+    }
+
+    /// Append `checkpoint`, discarding any redo tail past the cursor, and
+    /// make it the new current checkpoint.
+    pub fn push_checkpoint(&self, checkpoint: EditorCheckpoint) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.checkpoints.truncate(entries.cursor);
+        entries.checkpoints.push(checkpoint);
+        entries.cursor = entries.checkpoints.len();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.entries.lock().unwrap().cursor > 1
+    }
+
+    pub fn can_redo(&self) -> bool {
+        let entries = self.entries.lock().unwrap();
+        entries.cursor < entries.checkpoints.len()
+    }
+
+    /// Move the cursor back one checkpoint and return the one it now points
+    /// at, or `None` if already at the oldest checkpoint.
+    pub fn undo(&self) -> Option<EditorCheckpoint> {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.cursor <= 1 {
+            return None;
+        }
+        entries.cursor -= 1;
+        entries.checkpoints.get(entries.cursor - 1).cloned()
+    }
+
+    /// Move the cursor forward one checkpoint and return the one it now
+    /// points at, or `None` if already at the newest checkpoint.
+    pub fn redo(&self) -> Option<EditorCheckpoint> {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.cursor >= entries.checkpoints.len() {
+            return None;
+        }
+        let checkpoint = entries.checkpoints[entries.cursor].clone();
+        entries.cursor += 1;
+        Some(checkpoint)
+    }
+
+    pub fn checkpoints(&self) -> Vec<EditorCheckpoint> {
+        self.entries.lock().unwrap().checkpoints.clone()
+    }
+
+    /// Move the cursor to just past the checkpoint with `id` and return it.
+    pub fn restore_to(&self, id: &str) -> Result<EditorCheckpoint, String> {
+        let mut entries = self.entries.lock().unwrap();
+        let index = entries
+            .checkpoints
+            .iter()
+            .position(|c| c.id == id)
+            .ok_or_else(|| format!("no checkpoint with id {id}"))?;
+        entries.cursor = index + 1;
+        Ok(entries.checkpoints[index].clone())
+    }
+
+    /// Clone of the checkpoints paired with the cursor they were taken
+    /// alongside, under a single lock so the two can never be serialized
+    /// out of sync with each other.
+    pub fn snapshot(&self) -> (Vec<EditorCheckpoint>, usize) {
+        let entries = self.entries.lock().unwrap();
+        (entries.checkpoints.clone(), entries.cursor)
+    }
+
+    /// Replace the checkpoints/cursor pair wholesale, used to rehydrate a
+    /// persisted session. The cursor is clamped into `0..=checkpoints.len()`
+    /// since the snapshot is untrusted on-disk state that could be stale,
+    /// hand-edited, or (if ever written outside of [`Self::snapshot`]) torn.
+    pub fn restore(&self, checkpoints: Vec<EditorCheckpoint>, cursor: usize) {
+        let mut entries = self.entries.lock().unwrap();
+        let cursor = cursor.min(checkpoints.len());
+        entries.checkpoints = checkpoints;
+        entries.cursor = cursor;
+    }
+
+    /// Mark the session dirty and schedule a debounced flush to disk. Call
+    /// this from any command that mutates the checkpoint chain or the
+    /// dirty code buffer.
+    pub fn mark_dirty_and_schedule_flush(&self, app: &AppHandle) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(FLUSH_DEBOUNCE).await;
+            let history = app.state::<HistoryState>();
+            if history.generation.load(Ordering::SeqCst) == generation {
+                let _ = flush_now(&app);
+            }
+        });
+    }
+}
+
+impl Default for HistoryState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Synchronously serialize the in-memory history and dirty buffer to disk.
+/// Used by the debounced flush and by the window-close handler, which needs
+/// a final flush that can't be dropped mid-async-wait.
+pub fn flush_now(app: &AppHandle) -> Result<(), String> {
+    let history = app.state::<HistoryState>();
+    let editor = app.state::<EditorState>();
+
+    let (checkpoints, cursor) = history.snapshot();
+    let buffer = editor.current_code.lock().unwrap().clone();
+    let diagnostics = editor.diagnostics.lock().unwrap().clone();
+    let working_dir = editor.working_dir.lock().unwrap().clone();
+
+    store::save_session(
+        app,
+        &SessionSnapshot {
+            working_dir,
+            checkpoints,
+            cursor,
+            buffer,
+            diagnostics,
+            saved_at: store::now_ms(),
+        },
+    )
+}
+
+/// Look for a recoverable session on disk and, if one is newer than the
+/// file it was taken against, re-hydrate `HistoryState`/`EditorState` from
+/// it and tell the frontend so it can offer a recovery banner.
+pub fn restore_on_startup(app: &AppHandle) {
+    let Some(snapshot) = store::load_latest_session(app) else {
+        return;
+    };
+    if !store::is_recoverable(&snapshot) {
+        return;
+    }
+
+    let history = app.state::<HistoryState>();
+    history.restore(snapshot.checkpoints.clone(), snapshot.cursor);
+
+    let editor = app.state::<EditorState>();
+    *editor.current_code.lock().unwrap() = snapshot.buffer.clone();
+    *editor.diagnostics.lock().unwrap() = snapshot.diagnostics.clone();
+    *editor.working_dir.lock().unwrap() = snapshot.working_dir.clone();
+
+    let _ = app.emit("session:restored", &snapshot);
+    let _ = menu::refresh(app);
+}
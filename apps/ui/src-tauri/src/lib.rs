@@ -1,9 +1,13 @@
 mod cmd;
 mod history;
+mod menu;
+mod store;
 mod types;
 
 use cmd::{update_editor_state, update_working_dir, EditorState};
 use history::HistoryState;
+use menu::{MenuCommand, MenuHandles, RecentFilesMenu};
+use std::str::FromStr;
 use tauri::menu::{MenuBuilder, MenuItemBuilder, SubmenuBuilder};
 use tauri::{Emitter, Manager};
 
@@ -31,6 +35,7 @@ pub fn run() {
             cmd::history::can_undo,
             cmd::history::can_redo,
             cmd::history::get_checkpoint_by_id,
+            cmd::menu::refresh_menu_state,
         ])
         .setup(|app| {
             // Create app menu (About, Hide, Quit, etc.)
@@ -45,42 +50,43 @@ pub fn run() {
                 .build()?;
 
             // Create File menu
-            let file_menu = SubmenuBuilder::new(app, "File")
-                .item(
-                    &MenuItemBuilder::with_id("new", "New")
-                        .accelerator("CmdOrCtrl+N")
-                        .build(app)?,
-                )
-                .item(
-                    &MenuItemBuilder::with_id("open", "Open...")
-                        .accelerator("CmdOrCtrl+O")
-                        .build(app)?,
-                )
-                .separator()
-                .item(
-                    &MenuItemBuilder::with_id("save", "Save")
-                        .accelerator("CmdOrCtrl+S")
-                        .build(app)?,
-                )
-                .item(
-                    &MenuItemBuilder::with_id("save_as", "Save As...")
-                        .accelerator("CmdOrCtrl+Shift+S")
-                        .build(app)?,
-                )
-                .separator()
-                .item(&MenuItemBuilder::with_id("export_stl", "Export as STL...").build(app)?)
-                .item(&MenuItemBuilder::with_id("export_obj", "Export as OBJ...").build(app)?)
-                .item(&MenuItemBuilder::with_id("export_amf", "Export as AMF...").build(app)?)
-                .item(&MenuItemBuilder::with_id("export_3mf", "Export as 3MF...").build(app)?)
-                .item(&MenuItemBuilder::with_id("export_png", "Export as PNG...").build(app)?)
-                .item(&MenuItemBuilder::with_id("export_svg", "Export as SVG...").build(app)?)
-                .item(&MenuItemBuilder::with_id("export_dxf", "Export as DXF...").build(app)?)
-                .build()?;
+            let mut file_menu = SubmenuBuilder::new(app, "File");
+            let mut export_items = Vec::new();
+            let recent_submenu = SubmenuBuilder::new(app, "Open Recent").build()?;
+            for command in MenuCommand::FILE_COMMANDS {
+                if command.starts_group() {
+                    file_menu = file_menu.separator();
+                }
+                let mut item = MenuItemBuilder::with_id(command.id(), command.label());
+                if let Some(accelerator) = command.accelerator() {
+                    item = item.accelerator(accelerator);
+                }
+                let item = item.build(app)?;
+                if matches!(command, MenuCommand::Export(_)) {
+                    export_items.push(item.clone());
+                }
+                file_menu = file_menu.item(&item);
+                if command == MenuCommand::Open {
+                    file_menu = file_menu.item(&recent_submenu);
+                }
+            }
+            let file_menu = file_menu.build()?;
+
+            let recent_files_menu = RecentFilesMenu::new(recent_submenu);
+            recent_files_menu.rebuild(app, &store::list_recent_files(app.handle()))?;
 
-            // Create Edit menu
+            // Create Edit menu. Undo/Redo are custom items (rather than the
+            // platform-native predefined ones) so their enabled state can
+            // track `HistoryState`.
+            let undo_item = MenuItemBuilder::with_id(MenuCommand::Undo.id(), MenuCommand::Undo.label())
+                .accelerator(MenuCommand::Undo.accelerator().unwrap())
+                .build(app)?;
+            let redo_item = MenuItemBuilder::with_id(MenuCommand::Redo.id(), MenuCommand::Redo.label())
+                .accelerator(MenuCommand::Redo.accelerator().unwrap())
+                .build(app)?;
             let edit_menu = SubmenuBuilder::new(app, "Edit")
-                .undo()
-                .redo()
+                .item(&undo_item)
+                .item(&redo_item)
                 .separator()
                 .cut()
                 .copy()
@@ -89,6 +95,13 @@ pub fn run() {
                 .select_all()
                 .build()?;
 
+            app.manage(MenuHandles {
+                undo: undo_item,
+                redo: redo_item,
+                exports: export_items,
+            });
+            app.manage(recent_files_menu);
+
             let menu = MenuBuilder::new(app)
                 .item(&app_menu)
                 .item(&file_menu)
@@ -97,48 +110,54 @@ pub fn run() {
 
             app.set_menu(menu)?;
 
+            history::restore_on_startup(app.handle());
+            let _ = cmd::menu::refresh(app.handle());
+
             Ok(())
         })
         .on_menu_event(|app, event| {
-            // Emit events to frontend to handle the menu actions
-            let window = app.get_webview_window("main").unwrap();
-            match event.id().as_ref() {
-                "new" => {
-                    window.emit("menu:file:new", ()).unwrap();
-                }
-                "open" => {
-                    window.emit("menu:file:open", ()).unwrap();
-                }
-                "save" => {
-                    window.emit("menu:file:save", ()).unwrap();
-                }
-                "save_as" => {
-                    window.emit("menu:file:save_as", ()).unwrap();
-                }
-                "export_stl" => {
-                    window.emit("menu:file:export", "stl").unwrap();
-                }
-                "export_obj" => {
-                    window.emit("menu:file:export", "obj").unwrap();
-                }
-                "export_amf" => {
-                    window.emit("menu:file:export", "amf").unwrap();
-                }
-                "export_3mf" => {
-                    window.emit("menu:file:export", "3mf").unwrap();
+            let id = event.id().as_ref();
+
+            if id == menu::CLEAR_RECENT_ID {
+                let _ = store::clear_recent_files(app);
+                if let Some(recent) = app.try_state::<menu::RecentFilesMenu>() {
+                    let _ = recent.rebuild(app, &[]);
                 }
-                "export_png" => {
-                    window.emit("menu:file:export", "png").unwrap();
+                return;
+            }
+
+            if let Some(index) = menu::parse_recent_item_id(id) {
+                let path = app
+                    .try_state::<menu::RecentFilesMenu>()
+                    .and_then(|recent| recent.path_at(index));
+                if let Some(path) = path {
+                    let window = app.get_webview_window("main").unwrap();
+                    window.emit("menu:file:open_recent", path).unwrap();
                 }
-                "export_svg" => {
-                    window.emit("menu:file:export", "svg").unwrap();
+                return;
+            }
+
+            // Emit events to frontend to handle the menu actions
+            let Ok(command) = MenuCommand::from_str(id) else {
+                return;
+            };
+            let window = app.get_webview_window("main").unwrap();
+            match command {
+                MenuCommand::Export(format) => {
+                    window.emit(command.channel(), format.payload()).unwrap();
                 }
-                "export_dxf" => {
-                    window.emit("menu:file:export", "dxf").unwrap();
+                _ => {
+                    window.emit(command.channel(), ()).unwrap();
                 }
-                _ => {}
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // Force one last synchronous flush so a debounced write in
+            // flight isn't lost when the window closes.
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                let _ = history::flush_now(app_handle);
+            }
+        });
 }
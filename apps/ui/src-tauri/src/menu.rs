@@ -0,0 +1,272 @@
+//! Typed menu command registry. Each `MenuCommand` knows its own stable
+//! string id, accelerator and the event it emits, so adding a command (or
+//! an export format) is one enum variant instead of edits scattered across
+//! the builder, the dispatch match and the frontend contract.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::menu::{MenuItem, MenuItemBuilder, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Wry};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Stl,
+    Obj,
+    Amf,
+    ThreeMf,
+    Png,
+    Svg,
+    Dxf,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 7] = [
+        ExportFormat::Stl,
+        ExportFormat::Obj,
+        ExportFormat::Amf,
+        ExportFormat::ThreeMf,
+        ExportFormat::Png,
+        ExportFormat::Svg,
+        ExportFormat::Dxf,
+    ];
+
+    /// The value emitted to the frontend as the `menu:file:export` payload.
+    pub fn payload(&self) -> &'static str {
+        match self {
+            ExportFormat::Stl => "stl",
+            ExportFormat::Obj => "obj",
+            ExportFormat::Amf => "amf",
+            ExportFormat::ThreeMf => "3mf",
+            ExportFormat::Png => "png",
+            ExportFormat::Svg => "svg",
+            ExportFormat::Dxf => "dxf",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Stl => "Export as STL...",
+            ExportFormat::Obj => "Export as OBJ...",
+            ExportFormat::Amf => "Export as AMF...",
+            ExportFormat::ThreeMf => "Export as 3MF...",
+            ExportFormat::Png => "Export as PNG...",
+            ExportFormat::Svg => "Export as SVG...",
+            ExportFormat::Dxf => "Export as DXF...",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuCommand {
+    New,
+    Open,
+    Save,
+    SaveAs,
+    Export(ExportFormat),
+    Undo,
+    Redo,
+}
+
+impl MenuCommand {
+    /// File menu commands in build order.
+    pub const FILE_COMMANDS: [MenuCommand; 11] = [
+        MenuCommand::New,
+        MenuCommand::Open,
+        MenuCommand::Save,
+        MenuCommand::SaveAs,
+        MenuCommand::Export(ExportFormat::Stl),
+        MenuCommand::Export(ExportFormat::Obj),
+        MenuCommand::Export(ExportFormat::Amf),
+        MenuCommand::Export(ExportFormat::ThreeMf),
+        MenuCommand::Export(ExportFormat::Png),
+        MenuCommand::Export(ExportFormat::Svg),
+        MenuCommand::Export(ExportFormat::Dxf),
+    ];
+
+    /// Edit menu commands whose enabled state tracks `HistoryState`, in
+    /// build order.
+    pub const EDIT_COMMANDS: [MenuCommand; 2] = [MenuCommand::Undo, MenuCommand::Redo];
+
+    /// The stable string id used for `MenuItemBuilder::with_id` and parsed
+    /// back out of `event.id()`.
+    pub fn id(&self) -> &'static str {
+        match self {
+            MenuCommand::New => "new",
+            MenuCommand::Open => "open",
+            MenuCommand::Save => "save",
+            MenuCommand::SaveAs => "save_as",
+            MenuCommand::Export(ExportFormat::Stl) => "export_stl",
+            MenuCommand::Export(ExportFormat::Obj) => "export_obj",
+            MenuCommand::Export(ExportFormat::Amf) => "export_amf",
+            MenuCommand::Export(ExportFormat::ThreeMf) => "export_3mf",
+            MenuCommand::Export(ExportFormat::Png) => "export_png",
+            MenuCommand::Export(ExportFormat::Svg) => "export_svg",
+            MenuCommand::Export(ExportFormat::Dxf) => "export_dxf",
+            MenuCommand::Undo => "edit_undo",
+            MenuCommand::Redo => "edit_redo",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MenuCommand::New => "New",
+            MenuCommand::Open => "Open...",
+            MenuCommand::Save => "Save",
+            MenuCommand::SaveAs => "Save As...",
+            MenuCommand::Export(format) => format.label(),
+            MenuCommand::Undo => "Undo",
+            MenuCommand::Redo => "Redo",
+        }
+    }
+
+    pub fn accelerator(&self) -> Option<&'static str> {
+        match self {
+            MenuCommand::New => Some("CmdOrCtrl+N"),
+            MenuCommand::Open => Some("CmdOrCtrl+O"),
+            MenuCommand::Save => Some("CmdOrCtrl+S"),
+            MenuCommand::SaveAs => Some("CmdOrCtrl+Shift+S"),
+            MenuCommand::Export(_) => None,
+            MenuCommand::Undo => Some("CmdOrCtrl+Z"),
+            MenuCommand::Redo => Some("CmdOrCtrl+Shift+Z"),
+        }
+    }
+
+    /// The event channel this command emits to the frontend on.
+    pub fn channel(&self) -> &'static str {
+        match self {
+            MenuCommand::New => "menu:file:new",
+            MenuCommand::Open => "menu:file:open",
+            MenuCommand::Save => "menu:file:save",
+            MenuCommand::SaveAs => "menu:file:save_as",
+            MenuCommand::Export(_) => "menu:file:export",
+            MenuCommand::Undo => "menu:edit:undo",
+            MenuCommand::Redo => "menu:edit:redo",
+        }
+    }
+
+    /// True if this command is a separator boundary from the one before it
+    /// in [`MenuCommand::FILE_COMMANDS`] (i.e. it starts a new visual group).
+    pub fn starts_group(&self) -> bool {
+        matches!(self, MenuCommand::Save | MenuCommand::Export(ExportFormat::Stl))
+    }
+}
+
+impl fmt::Display for MenuCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for MenuCommand {
+    type Err = String;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        MenuCommand::FILE_COMMANDS
+            .into_iter()
+            .chain(MenuCommand::EDIT_COMMANDS)
+            .find(|cmd| cmd.id() == id)
+            .ok_or_else(|| format!("unknown menu command id: {id}"))
+    }
+}
+
+/// Handles to the `MenuItem`s whose enabled state tracks live app state,
+/// kept in managed state so commands can call `set_enabled` on them
+/// without rebuilding the menu.
+pub struct MenuHandles {
+    pub undo: MenuItem<Wry>,
+    pub redo: MenuItem<Wry>,
+    pub exports: Vec<MenuItem<Wry>>,
+}
+
+impl MenuHandles {
+    /// Apply freshly-computed enabled state to every tracked item.
+    pub fn apply(&self, can_undo: bool, can_redo: bool, can_export: bool) -> tauri::Result<()> {
+        self.undo.set_enabled(can_undo)?;
+        self.redo.set_enabled(can_redo)?;
+        for item in &self.exports {
+            item.set_enabled(can_export)?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Recent files submenu
+// ============================================================================
+
+pub const CLEAR_RECENT_ID: &str = "clear_recent";
+const RECENT_ITEM_PREFIX: &str = "open_recent_";
+
+pub fn recent_item_id(index: usize) -> String {
+    format!("{RECENT_ITEM_PREFIX}{index}")
+}
+
+/// Parse an `open_recent_<index>` menu id back into its index into the
+/// current MRU list.
+pub fn parse_recent_item_id(id: &str) -> Option<usize> {
+    id.strip_prefix(RECENT_ITEM_PREFIX)?.parse().ok()
+}
+
+fn display_label(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// The "Open Recent" submenu under File, rebuilt in place whenever the MRU
+/// list changes rather than requiring the whole File menu to be rebuilt.
+///
+/// `rendered_paths` is the exact list the submenu's items were last built
+/// from, so a click's index always resolves against what the user actually
+/// saw, rather than against a freshly re-read (and possibly re-filtered)
+/// copy of the MRU list that could have since diverged from it.
+pub struct RecentFilesMenu {
+    pub submenu: Submenu<Wry>,
+    rendered_paths: Mutex<Vec<String>>,
+}
+
+impl RecentFilesMenu {
+    pub fn new(submenu: Submenu<Wry>) -> Self {
+        Self {
+            submenu,
+            rendered_paths: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn rebuild(&self, app: &AppHandle, paths: &[String]) -> tauri::Result<()> {
+        for item in self.submenu.items()? {
+            self.submenu.remove(&item)?;
+        }
+
+        if paths.is_empty() {
+            let placeholder = MenuItemBuilder::with_id("open_recent_empty", "No Recent Files")
+                .enabled(false)
+                .build(app)?;
+            self.submenu.append(&placeholder)?;
+        } else {
+            for (index, path) in paths.iter().enumerate() {
+                let item =
+                    MenuItemBuilder::with_id(recent_item_id(index), display_label(path))
+                        .build(app)?;
+                self.submenu.append(&item)?;
+            }
+        }
+
+        self.submenu.append(&PredefinedMenuItem::separator(app)?)?;
+        let clear_item = MenuItemBuilder::with_id(CLEAR_RECENT_ID, "Clear Recent").build(app)?;
+        self.submenu.append(&clear_item)?;
+
+        *self.rendered_paths.lock().unwrap() = paths.to_vec();
+
+        Ok(())
+    }
+
+    /// The path the item at `index` was built from, per the most recent
+    /// `rebuild` call.
+    pub fn path_at(&self, index: usize) -> Option<String> {
+        self.rendered_paths.lock().unwrap().get(index).cloned()
+    }
+}
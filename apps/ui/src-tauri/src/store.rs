@@ -0,0 +1,152 @@
+//! On-disk persistence for editor sessions, keyed by working directory.
+
+use crate::types::{Diagnostic, EditorCheckpoint};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+/// Full on-disk mirror of a session's undo/redo history and dirty buffer,
+/// written on a debounced flush so a crash or restart can recover it.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SessionSnapshot {
+    pub working_dir: Option<String>,
+    pub checkpoints: Vec<EditorCheckpoint>,
+    pub cursor: usize,
+    pub buffer: String,
+    /// Diagnostics for `buffer`, so a recovered session's Export menu items
+    /// reflect the recovered buffer's compile state immediately, rather
+    /// than only after the next history operation repopulates them.
+    pub diagnostics: Vec<Diagnostic>,
+    pub saved_at: i64,
+}
+
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn sessions_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("sessions");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+fn session_key(working_dir: &Option<String>) -> String {
+    let mut hasher = DefaultHasher::new();
+    working_dir
+        .as_deref()
+        .unwrap_or("__untitled__")
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+fn session_path(app: &AppHandle, working_dir: &Option<String>) -> Result<PathBuf, String> {
+    Ok(sessions_dir(app)?.join(format!("{}.json", session_key(working_dir))))
+}
+
+pub fn save_session(app: &AppHandle, snapshot: &SessionSnapshot) -> Result<(), String> {
+    let path = session_path(app, &snapshot.working_dir)?;
+    let data = serde_json::to_string_pretty(snapshot).map_err(|e| e.to_string())?;
+    fs::write(path, data).map_err(|e| e.to_string())
+}
+
+/// Find the most recently flushed session across all working directories,
+/// used to offer recovery on startup before any working dir is known.
+pub fn load_latest_session(app: &AppHandle) -> Option<SessionSnapshot> {
+    let dir = sessions_dir(app).ok()?;
+    let mut latest: Option<SessionSnapshot> = None;
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let Ok(data) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        let Ok(snapshot) = serde_json::from_str::<SessionSnapshot>(&data) else {
+            continue;
+        };
+        if latest.as_ref().is_none_or(|s| snapshot.saved_at > s.saved_at) {
+            latest = Some(snapshot);
+        }
+    }
+    latest
+}
+
+/// A snapshot is worth recovering only if it genuinely postdates the file
+/// it was taken against (or there is no file yet, for an untitled buffer).
+pub fn is_recoverable(snapshot: &SessionSnapshot) -> bool {
+    match &snapshot.working_dir {
+        None => !snapshot.buffer.is_empty(),
+        Some(path) => match fs::metadata(path).and_then(|m| m.modified()) {
+            Ok(modified) => {
+                let file_ms = modified
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_millis() as i64)
+                    .unwrap_or(0);
+                snapshot.saved_at > file_ms
+            }
+            Err(_) => true,
+        },
+    }
+}
+
+// ============================================================================
+// Recent files (MRU)
+// ============================================================================
+
+const MAX_RECENT_FILES: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct RecentFiles {
+    paths: Vec<String>,
+}
+
+fn recent_files_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join("recent_files.json"))
+}
+
+fn read_recent_files(app: &AppHandle) -> RecentFiles {
+    recent_files_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+fn write_recent_files(app: &AppHandle, recent: &RecentFiles) -> Result<(), String> {
+    let path = recent_files_path(app)?;
+    let data = serde_json::to_string_pretty(recent).map_err(|e| e.to_string())?;
+    fs::write(path, data).map_err(|e| e.to_string())
+}
+
+/// Push `path` to the front of the MRU list, deduplicating and capping it.
+pub fn push_recent_file(app: &AppHandle, path: &str) -> Result<Vec<String>, String> {
+    let mut recent = read_recent_files(app);
+    recent.paths.retain(|p| p != path);
+    recent.paths.insert(0, path.to_string());
+    recent.paths.truncate(MAX_RECENT_FILES);
+    write_recent_files(app, &recent)?;
+    Ok(recent.paths)
+}
+
+/// Recent files that still exist on disk, newest first.
+pub fn list_recent_files(app: &AppHandle) -> Vec<String> {
+    read_recent_files(app)
+        .paths
+        .into_iter()
+        .filter(|p| fs::metadata(p).is_ok())
+        .collect()
+}
+
+pub fn clear_recent_files(app: &AppHandle) -> Result<(), String> {
+    write_recent_files(app, &RecentFiles::default())
+}
@@ -42,6 +42,31 @@ pub struct EditorCheckpoint {
     pub change_type: ChangeType,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffHunkTag {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A contiguous block of changed lines, with the line ranges on both sides
+/// so the frontend can jump the editor to the right spot without
+/// re-parsing the unified `diff` string.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub tag: DiffHunkTag,
+    /// 1-indexed, inclusive. Empty (no lines on that side) when `end < start`.
+    pub old_start: usize,
+    pub old_end: usize,
+    pub new_start: usize,
+    pub new_end: usize,
+    /// Whether this hunk's line range overlaps a diagnostic (of any
+    /// severity) on either checkpoint: the old range is checked against the
+    /// `from` checkpoint's diagnostics, the new range against the `to` one's.
+    pub overlaps_diagnostic: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CheckpointDiff {
     pub from_id: String,
@@ -49,4 +74,18 @@ pub struct CheckpointDiff {
     pub diff: String,
     pub added_lines: usize,
     pub removed_lines: usize,
+    pub hunks: Vec<DiffHunk>,
+}
+
+// ============================================================================
+// Menu State
+// ============================================================================
+
+/// Mirror of the Undo/Redo/Export menu items' enabled state, emitted so a
+/// toolbar can stay in sync without re-deriving it from history/diagnostics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuState {
+    pub can_undo: bool,
+    pub can_redo: bool,
+    pub can_export: bool,
 }